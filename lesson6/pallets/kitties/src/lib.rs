@@ -2,30 +2,46 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use codec::{Encode, Decode};
-use frame_support::{decl_module, decl_storage, decl_error, ensure, StorageValue, StorageMap, traits::Randomness, Parameter};
+use frame_support::{decl_module, decl_storage, decl_error, decl_event, ensure, StorageValue, StorageMap, traits::{Currency, ExistenceRequirement, Randomness, ReservableCurrency}, Parameter};
 use sp_io::hashing::blake2_128;
 use frame_system::ensure_signed;
-use sp_runtime::{DispatchError, DispatchResult, traits::{AtLeast32Bit, Bounded, Member}};
+use sp_runtime::{DispatchError, traits::{AtLeast32Bit, Bounded, Member}};
+use sp_std::vec::Vec;
 
+// feature指定生效环境 测试环境才会用到定义在std下，derive约束打印、比较特征，链上资源宝贵减小wasm环境大小
+#[cfg_attr(feature = "std", derive(Debug, PartialEq, Eq))]
 #[derive(Encode, Decode)]
-pub struct Kitty(pub [u8; 16]);
+pub struct Kitty<T: Trait> {
+	pub dna: [u8; 16],
+	// 繁殖深度，新生小猫为 0，繁殖出的小猫取双亲中较大的一代 + 1
+	pub gen: u64,
+	// 创世小猫没有双亲，繁殖出的小猫记录双亲编号用于追溯血统
+	pub parents: Option<(T::KittyId, T::KittyId)>,
+}
 
-// feature指定生效环境 测试环境才会用到定义在std下，derive约束打印、比较特征，链上资源宝贵减小wasm环境大小
 #[cfg_attr(feature = "std", derive(Debug, PartialEq,Eq))]
 #[derive(Encode, Decode)]
 pub struct KittyLinkedItem<T: Trait> {
 	pub prev: Option<T::KittyId>,
 	pub next: Option<T::KittyId>,
 }
+
+// 小猫定价使用链上货币单位，余额类型跟随 Currency 关联类型走
+pub type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+
 pub trait Trait: frame_system::Trait {
 	// 定义小猫ID 使用的时候指定类型;加上限定类型 Member代表该类型可以放到结构体或者枚举中使用
 	type KittyId: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy;
+	// 买卖小猫需要转账，交由 Currency 关联类型对接 pallet_balances
+	type Currency: ReservableCurrency<Self::AccountId>;
+	// 外部世界（钱包、浏览器、索引器）通过事件感知小猫状态变化
+	type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
 }
 
 decl_storage! {
 	trait Store for Module<T: Trait> as Kitties {
 		/// Stores all the kitties, key is the kitty id / index
-		pub Kitties get(fn kitties): map hasher(blake2_128_concat) T::KittyId => Option<Kitty>;
+		pub Kitties get(fn kitties): map hasher(blake2_128_concat) T::KittyId => Option<Kitty<T>>;
 		/// Stores the total number of kitties. i.e. the next kitty index
 		pub KittiesCount get(fn kitties_count): T::KittyId;
 
@@ -36,6 +52,17 @@ decl_storage! {
 		 // 用户小猫相关数据使用自定义的链表数据结构保存
 		 pub OwnedKitties get(fn owned_kitties): map hasher(blake2_128_concat) (T::AccountId,Option<T::KittyId>) => Option<KittyLinkedItem<T>>;
 
+		/// Get kitty owner by kitty id, needed to settle a `buy` without the buyer knowing the seller
+		pub KittyOwners get(fn kitty_owner): map hasher(blake2_128_concat) T::KittyId => Option<T::AccountId>;
+
+		/// Price set by the owner, kitty is for sale while this is `Some`
+		pub KittyPrices get(fn kitty_price): map hasher(blake2_128_concat) T::KittyId => Option<BalanceOf<T>>;
+
+		/// Bumped on every `random_value` call so repeated creates within one block don't collide
+		pub Nonce get(fn nonce): u64;
+
+		/// Reverse lookup from DNA to kitty id, used to detect DNA collisions
+		pub KittyDnaIndex get(fn kitty_dna_index): map hasher(blake2_128_concat) [u8; 16] => Option<T::KittyId>;
 	}
 }
 
@@ -45,13 +72,38 @@ decl_error! {
 		InvalidKittyId,
 		RequireDifferentParent,
 		RequireOwner,
+		KittyNotForSale,
+		PriceTooLow,
+		DuplicateDna,
+		GenOverflow,
 	}
 }
 
+decl_event!(
+	pub enum Event<T> where
+		AccountId = <T as frame_system::Trait>::AccountId,
+		KittyId = <T as Trait>::KittyId,
+		Balance = BalanceOf<T>,
+	{
+		/// A kitty was created. \[owner, kitty_id\]
+		KittyCreated(AccountId, KittyId),
+		/// Two kitties were bred into a new one. \[owner, kitty_id_1, kitty_id_2, new_kitty_id\]
+		KittyBred(AccountId, KittyId, KittyId, KittyId),
+		/// A kitty was transferred. \[from, to, kitty_id\]
+		KittyTransferred(AccountId, AccountId, KittyId),
+		/// A kitty's sale price was updated, `None` means it was taken off the market. \[owner, kitty_id, price\]
+		PriceSet(AccountId, KittyId, Option<Balance>),
+		/// A kitty was sold. \[seller, buyer, kitty_id, price\]
+		KittySold(AccountId, AccountId, KittyId, Balance),
+	}
+);
+
 decl_module! {
 	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
 		type Error = Error<T>;
 
+		fn deposit_event() = default;
+
 		/// Create a new kitty
 		#[weight = 0]
 		pub fn create(origin) {
@@ -59,15 +111,16 @@ decl_module! {
 			// 生成新猫ID
 			let new_kitty_id = Self::next_kitty_id()?;
 
-			// Generate a random 128bit value 生成新猫DNA
-			let dna = Self::random_value(&sender);
+			// Generate a random 128bit value 生成新猫DNA，并保证不与已有小猫重复
+			let dna = Self::generate_dna(|| Self::random_value(&sender))?;
 
-		 	// Create and store kitty 生成新猫
-			let new_kitty = Kitty(dna);
+		 	// Create and store kitty 生成新猫，创世一代没有双亲
+			let new_kitty = Kitty { dna, gen: 0, parents: None };
 
             // 新猫信息存储
             Self::insert_kitty(&sender, new_kitty_id, new_kitty);
 
+            Self::deposit_event(RawEvent::KittyCreated(sender, new_kitty_id));
 		}
 
 		/// Breed kitties  繁殖小猫
@@ -75,20 +128,61 @@ decl_module! {
 		pub fn breed(origin, kitty_id_1: T::KittyId, kitty_id_2: T::KittyId) {
 			let sender = ensure_signed(origin)?;
 
-			Self::do_breed(&sender, kitty_id_1, kitty_id_2)?;
+			let new_kitty_id = Self::do_breed(&sender, kitty_id_1, kitty_id_2)?;
+
+			Self::deposit_event(RawEvent::KittyBred(sender, kitty_id_1, kitty_id_2, new_kitty_id));
 		}
 		// transfer kitty to another
 		#[weight = 0]
 		pub fn transfer(origin, to: T::AccountId,kitty_id: T::KittyId){
 			// 小猫转移
 			let sender = ensure_signed(origin)?;
-			if let Some(item) = <OwnedKitties<T>>::take((&sender,Some(kitty_id))) {
-				ensure!(item.prev != None || item.next != None,<Error<T>>::RequireOwner);
-		    }
-			// 将数据添加到新主人数据中
-			<OwnedKitties<T>>::append(&to,kitty_id);
+			ensure!(Self::is_kitty_owner(&sender, kitty_id), Error::<T>::RequireOwner);
+
 			// 从原主人数据中移除掉
 			<OwnedKitties<T>>::remove(&sender,kitty_id);
+			// 将数据添加到新主人数据中
+			<OwnedKitties<T>>::append(&to,kitty_id);
+			<KittyOwners<T>>::insert(kitty_id, &to);
+			// 换了新主人，原来的挂单价格作废，新主人的小猫默认不出售
+			<KittyPrices<T>>::remove(kitty_id);
+
+			Self::deposit_event(RawEvent::KittyTransferred(sender, to, kitty_id));
+		}
+
+		/// List a kitty for sale, or take it off the market by passing `None`
+		#[weight = 0]
+		pub fn set_price(origin, kitty_id: T::KittyId, price: Option<BalanceOf<T>>) {
+			let sender = ensure_signed(origin)?;
+			ensure!(Self::is_kitty_owner(&sender, kitty_id), Error::<T>::RequireOwner);
+
+			if let Some(price) = price {
+				<KittyPrices<T>>::insert(kitty_id, price);
+			} else {
+				<KittyPrices<T>>::remove(kitty_id);
+			}
+
+			Self::deposit_event(RawEvent::PriceSet(sender, kitty_id, price));
+		}
+
+		/// Buy a kitty that is currently for sale, paying at most `max_price`
+		#[weight = 0]
+		pub fn buy(origin, kitty_id: T::KittyId, max_price: BalanceOf<T>) {
+			let sender = ensure_signed(origin)?;
+			let owner = Self::kitty_owner(kitty_id).ok_or(Error::<T>::InvalidKittyId)?;
+			let price = Self::kitty_price(kitty_id).ok_or(Error::<T>::KittyNotForSale)?;
+			ensure!(price <= max_price, Error::<T>::PriceTooLow);
+
+			T::Currency::transfer(&sender, &owner, price, ExistenceRequirement::KeepAlive)?;
+
+			// 小猫转移到买家名下
+			<OwnedKitties<T>>::remove(&owner, kitty_id);
+			<OwnedKitties<T>>::append(&sender, kitty_id);
+			<KittyOwners<T>>::insert(kitty_id, &sender);
+			// 成交后清空挂单
+			<KittyPrices<T>>::remove(kitty_id);
+
+			Self::deposit_event(RawEvent::KittySold(owner, sender, kitty_id, price));
 		}
 	}
 }
@@ -157,20 +251,57 @@ impl<T: Trait> OwnedKitties<T> {
 	}
 }
 
+/// Walks an account's owned-kitties linked list from `next` to the end
+pub struct OwnedKittiesIterator<T: Trait> {
+	account: T::AccountId,
+	next: Option<T::KittyId>,
+}
+
+impl<T: Trait> Iterator for OwnedKittiesIterator<T> {
+	type Item = T::KittyId;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let current = self.next?;
+		self.next = OwnedKitties::<T>::read(&self.account, Some(current)).next;
+		Some(current)
+	}
+}
+
 fn combine_dna(dna1: u8, dna2: u8, selector: u8) -> u8 {
 	(selector & dna1) | (!selector & dna2)
 }
 
+// 同一区块内重复重投骰子的上限，超过后认为概率异常，直接报错而不是死循环
+const MAX_DNA_GENERATE_ATTEMPTS: u8 = 5;
+
 impl<T: Trait> Module<T> {
 	fn random_value(sender: &T::AccountId) -> [u8; 16] {
+		// collective-flip 的随机种子在同一区块内不变，叠加自增 nonce 避免同账户连续 create 撞车
+		let nonce = Nonce::mutate(|nonce| {
+			let current = *nonce;
+			*nonce = nonce.wrapping_add(1);
+			current
+		});
 		let payload = (
 			<pallet_randomness_collective_flip::Module<T> as Randomness<T::Hash>>::random_seed(),
 			&sender,
 			<frame_system::Module<T>>::extrinsic_index(),
+			nonce,
 		);
 		payload.using_encoded(blake2_128)
 	}
 
+	// 反复调用 `next` 直至生成一个尚未被任何小猫使用的 DNA，超过上限则报错
+	fn generate_dna(mut next: impl FnMut() -> [u8; 16]) -> sp_std::result::Result<[u8; 16], DispatchError> {
+		for _ in 0..MAX_DNA_GENERATE_ATTEMPTS {
+			let dna = next();
+			if !<KittyDnaIndex<T>>::contains_key(dna) {
+				return Ok(dna);
+			}
+		}
+		Err(Error::<T>::DuplicateDna.into())
+	}
+
 	fn next_kitty_id() -> sp_std::result::Result<T::KittyId, DispatchError> {
 		let kitty_id = Self::kitties_count();
 		if kitty_id == T::KittyId::max_value() {
@@ -184,15 +315,57 @@ impl<T: Trait> Module<T> {
 		<OwnedKitties<T>>::append(&owner,kitty_id);
 	}
 
-	fn insert_kitty(owner: &T::AccountId, kitty_id: T::KittyId, kitty: Kitty) {
+	fn insert_kitty(owner: &T::AccountId, kitty_id: T::KittyId, kitty: Kitty<T>) {
 		// Create and store kitty
+		<KittyDnaIndex<T>>::insert(kitty.dna, kitty_id);
 		Kitties::<T>::insert(kitty_id, kitty);
 		KittiesCount::<T>::put(kitty_id + 1.into());
+		<KittyOwners<T>>::insert(kitty_id, owner);
+		// 新生成的小猫编号此前未被使用过，保证不会继承陈旧的挂单
+		<KittyPrices<T>>::remove(kitty_id);
 
 		Self::insert_owned_kitty(owner, kitty_id);
 	}
 
-	fn do_breed(sender: &T::AccountId, kitty_id_1: T::KittyId, kitty_id_2: T::KittyId) -> DispatchResult {
+	fn is_kitty_owner(who: &T::AccountId, kitty_id: T::KittyId) -> bool {
+		<OwnedKitties<T>>::contains_key((who, Some(kitty_id)))
+	}
+
+	/// Iterate over every kitty owned by `account`, oldest first
+	pub fn owned_kitties_iter(account: T::AccountId) -> OwnedKittiesIterator<T> {
+		let next = OwnedKitties::<T>::read_head(&account).next;
+		OwnedKittiesIterator { account, next }
+	}
+
+	/// Page through an account's kitties, starting after `start` (or from the beginning if `None`)
+	/// and returning at most `limit` ids along with the cursor to pass as `start` for the next page
+	/// (`None` once the list is exhausted). The cursor is the last id of the page; an empty page
+	/// (`limit` of zero) hands `start` back unchanged as the cursor.
+	pub fn owned_kitties_page(account: T::AccountId, start: Option<T::KittyId>, limit: u32) -> (Vec<T::KittyId>, Option<T::KittyId>) {
+		let mut next = match start {
+			Some(start) => OwnedKitties::<T>::read(&account, Some(start)).next,
+			None => OwnedKitties::<T>::read_head(&account).next,
+		};
+
+		let mut page = Vec::new();
+		while let Some(kitty_id) = next {
+			if page.len() as u32 >= limit {
+				break;
+			}
+			page.push(kitty_id);
+			next = OwnedKitties::<T>::read(&account, Some(kitty_id)).next;
+		}
+
+		// the next page starts after the last id returned here, so that id is the cursor
+		let cursor = match next {
+			Some(_) => page.last().copied().or(start),
+			None => None,
+		};
+
+		(page, cursor)
+	}
+
+	fn do_breed(sender: &T::AccountId, kitty_id_1: T::KittyId, kitty_id_2: T::KittyId) -> sp_std::result::Result<T::KittyId, DispatchError> {
 		let kitty1 = Self::kitties(kitty_id_1).ok_or(Error::<T>::InvalidKittyId)?;
 		let kitty2 = Self::kitties(kitty_id_2).ok_or(Error::<T>::InvalidKittyId)?;
 
@@ -200,21 +373,28 @@ impl<T: Trait> Module<T> {
 
 		let kitty_id = Self::next_kitty_id()?;
 
-		let kitty1_dna = kitty1.0;
-		let kitty2_dna = kitty2.0;
+		let kitty1_dna = kitty1.dna;
+		let kitty2_dna = kitty2.dna;
 
-		// Generate a random 128bit value
-		let selector = Self::random_value(&sender);
-		let mut new_dna = [0u8; 16];
+		// Combine parents and a random selector to create new kitty, re-rolling the selector on a DNA collision
+		let new_dna = Self::generate_dna(|| {
+			let selector = Self::random_value(&sender);
+			let mut dna = [0u8; 16];
+			for i in 0..kitty1_dna.len() {
+				dna[i] = combine_dna(kitty1_dna[i], kitty2_dna[i], selector[i]);
+			}
+			dna
+		})?;
 
-		// Combine parents and selector to create new kitty
-		for i in 0..kitty1_dna.len() {
-			new_dna[i] = combine_dna(kitty1_dna[i], kitty2_dna[i], selector[i]);
-		}
+		// 繁殖出的小猫取双亲中较大的一代 + 1，并记录双亲编号；用 checked_add 防止代数溢出
+		let gen = sp_std::cmp::max(kitty1.gen, kitty2.gen)
+			.checked_add(1)
+			.ok_or(Error::<T>::GenOverflow)?;
+		let new_kitty = Kitty { dna: new_dna, gen, parents: Some((kitty_id_1, kitty_id_2)) };
 
-		Self::insert_kitty(sender, kitty_id, Kitty(new_dna));
+		Self::insert_kitty(sender, kitty_id, new_kitty);
 
-		Ok(())
+		Ok(kitty_id)
 	}
 }
 
@@ -224,7 +404,7 @@ mod tests {
 	use super::*;
 
 	use sp_core::H256;
-	use frame_support::{impl_outer_origin, parameter_types, weights::Weight};
+	use frame_support::{assert_ok, assert_noop, impl_outer_origin, impl_outer_event, parameter_types, weights::Weight};
 	use sp_runtime::{
 		traits::{BlakeTwo256, IdentityLookup}, testing::Header, Perbill,
 	};
@@ -234,6 +414,18 @@ mod tests {
 		pub enum Origin for Test {}
 	}
 
+	mod kitties {
+		pub use crate::Event;
+	}
+
+	impl_outer_event! {
+		pub enum TestEvent for Test {
+			system<T>,
+			pallet_balances<T>,
+			kitties<T>,
+		}
+	}
+
 	// For testing the module, we construct most of a mock runtime. This means
 	// first constructing a configuration type (`Test`) which `impl`s each of the
 	// configuration traits of modules we want to use.
@@ -255,7 +447,7 @@ mod tests {
 		type AccountId = u64;
 		type Lookup = IdentityLookup<Self::AccountId>;
 		type Header = Header;
-		type Event = ();
+		type Event = TestEvent;
 		type BlockHashCount = BlockHashCount;
 		type MaximumBlockWeight = MaximumBlockWeight;
 		type DbWeight = ();
@@ -270,15 +462,42 @@ mod tests {
 		type OnNewAccount = ();
 		type OnKilledAccount = ();
 	}
+	parameter_types! {
+		pub const ExistentialDeposit: u64 = 1;
+	}
+	impl pallet_balances::Trait for Test {
+		type Balance = u64;
+		type Event = TestEvent;
+		type DustRemoval = ();
+		type ExistentialDeposit = ExistentialDeposit;
+		type AccountStore = frame_system::Module<Test>;
+		type WeightInfo = ();
+	}
 	impl Trait for Test {
 		type KittyId = u32;
+		type Currency = pallet_balances::Module<Test>;
+		type Event = TestEvent;
 	}
 	type OwnedKittiesTest = OwnedKitties<Test>;
+	type KittiesModule = Module<Test>;
+	type Balances = pallet_balances::Module<Test>;
+	type System = frame_system::Module<Test>;
+
+	// Returns the most recently deposited event, for asserting a dispatchable actually
+	// wired up `deposit_event` rather than just mutating storage.
+	fn last_event() -> TestEvent {
+		System::events().pop().expect("an event was deposited").event
+	}
 
 	// This function basically just builds a genesis storage key/value store according to
-	// our desired mockup.
+	// our desired mockup. Seeds accounts 1 and 2 with a balance so marketplace tests can
+	// exercise `T::Currency::transfer` without a separate genesis config per test.
 	fn new_test_ext() -> sp_io::TestExternalities {
-		system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
+		let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+		pallet_balances::GenesisConfig::<Test> {
+			balances: vec![(1, 1000), (2, 1000)],
+		}.assimilate_storage(&mut t).unwrap();
+		t.into()
 	}
 
 	#[test]
@@ -347,4 +566,227 @@ mod tests {
 			next: None,
 		}));
 	}
+
+	#[test]
+	fn buy_moves_balance_and_ownership() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(KittiesModule::create(Origin::signed(1)));
+			assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, Some(100)));
+
+			assert_ok!(KittiesModule::buy(Origin::signed(2), 0, 100));
+
+			assert_eq!(Balances::free_balance(1), 1100);
+			assert_eq!(Balances::free_balance(2), 900);
+			assert_eq!(KittiesModule::kitty_owner(0), Some(2));
+			assert_eq!(KittiesModule::kitty_price(0), None);
+		});
+	}
+
+	#[test]
+	fn buy_fails_when_not_for_sale() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(KittiesModule::create(Origin::signed(1)));
+
+			assert_noop!(
+				KittiesModule::buy(Origin::signed(2), 0, 1000),
+				Error::<Test>::KittyNotForSale
+			);
+		});
+	}
+
+	#[test]
+	fn buy_fails_when_price_too_low() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(KittiesModule::create(Origin::signed(1)));
+			assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, Some(100)));
+
+			assert_noop!(
+				KittiesModule::buy(Origin::signed(2), 0, 50),
+				Error::<Test>::PriceTooLow
+			);
+		});
+	}
+
+	#[test]
+	fn transfer_clears_stale_price() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(KittiesModule::create(Origin::signed(1)));
+			assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, Some(100)));
+
+			assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 0));
+
+			assert_eq!(KittiesModule::kitty_price(0), None);
+			// new owner never re-listed it, so the kitty is no longer purchasable at the
+			// seller's old price (or at all)
+			assert_noop!(
+				KittiesModule::buy(Origin::signed(3), 0, 1000),
+				Error::<Test>::KittyNotForSale
+			);
+		});
+	}
+
+	#[test]
+	fn transfer_requires_owner() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(KittiesModule::create(Origin::signed(1)));
+
+			assert_noop!(
+				KittiesModule::transfer(Origin::signed(2), 3, 0),
+				Error::<Test>::RequireOwner
+			);
+			assert_eq!(KittiesModule::kitty_owner(0), Some(1));
+		});
+	}
+
+	#[test]
+	fn set_price_requires_owner() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(KittiesModule::create(Origin::signed(1)));
+
+			assert_noop!(
+				KittiesModule::set_price(Origin::signed(2), 0, Some(100)),
+				Error::<Test>::RequireOwner
+			);
+		});
+	}
+
+	// frame_system doesn't record events at block 0, so event tests start a block first.
+
+	#[test]
+	fn create_deposits_event() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			assert_ok!(KittiesModule::create(Origin::signed(1)));
+
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::KittyCreated(1, 0)));
+		});
+	}
+
+	#[test]
+	fn breed_deposits_event() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			assert_ok!(KittiesModule::create(Origin::signed(1)));
+			assert_ok!(KittiesModule::create(Origin::signed(1)));
+			assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::KittyBred(1, 0, 1, 2)));
+		});
+	}
+
+	#[test]
+	fn transfer_deposits_event() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			assert_ok!(KittiesModule::create(Origin::signed(1)));
+			assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 0));
+
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::KittyTransferred(1, 2, 0)));
+		});
+	}
+
+	#[test]
+	fn set_price_deposits_event() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			assert_ok!(KittiesModule::create(Origin::signed(1)));
+			assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, Some(100)));
+
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::PriceSet(1, 0, Some(100))));
+		});
+	}
+
+	#[test]
+	fn buy_deposits_event() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			assert_ok!(KittiesModule::create(Origin::signed(1)));
+			assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, Some(100)));
+			assert_ok!(KittiesModule::buy(Origin::signed(2), 0, 100));
+
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::KittySold(1, 2, 0, 100)));
+		});
+	}
+
+	#[test]
+	fn generate_dna_errors_after_max_attempts_on_collision() {
+		new_test_ext().execute_with(|| {
+			let dna = [7u8; 16];
+			<KittyDnaIndex<Test>>::insert(dna, 0);
+
+			assert_eq!(
+				Module::<Test>::generate_dna(|| dna),
+				Err(Error::<Test>::DuplicateDna.into())
+			);
+		});
+	}
+
+	#[test]
+	fn nonce_changes_dna_within_the_same_block() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(KittiesModule::create(Origin::signed(1)));
+			assert_ok!(KittiesModule::create(Origin::signed(1)));
+
+			let kitty0 = KittiesModule::kitties(0).unwrap();
+			let kitty1 = KittiesModule::kitties(1).unwrap();
+
+			assert_ne!(kitty0.dna, kitty1.dna);
+		});
+	}
+
+	#[test]
+	fn do_breed_errors_on_generation_overflow() {
+		new_test_ext().execute_with(|| {
+			let parent1 = Kitty::<Test> { dna: [1u8; 16], gen: u64::max_value(), parents: None };
+			let parent2 = Kitty::<Test> { dna: [2u8; 16], gen: u64::max_value(), parents: None };
+			<Kitties<Test>>::insert(0, parent1);
+			<Kitties<Test>>::insert(1, parent2);
+
+			assert_eq!(
+				Module::<Test>::do_breed(&1, 0, 1),
+				Err(Error::<Test>::GenOverflow.into())
+			);
+		});
+	}
+
+	#[test]
+	fn owned_kitties_page_paginates_with_a_cursor() {
+		new_test_ext().execute_with(|| {
+			OwnedKittiesTest::append(&0, 1);
+			OwnedKittiesTest::append(&0, 2);
+			OwnedKittiesTest::append(&0, 3);
+
+			let (first_page, cursor) = Module::<Test>::owned_kitties_page(0, None, 2);
+			assert_eq!(first_page, vec![1, 2]);
+			assert_eq!(cursor, Some(2));
+
+			let (second_page, cursor) = Module::<Test>::owned_kitties_page(0, cursor, 2);
+			assert_eq!(second_page, vec![3]);
+			assert_eq!(cursor, None);
+		});
+	}
+
+	#[test]
+	fn owned_kitties_page_with_limit_zero_returns_nothing() {
+		new_test_ext().execute_with(|| {
+			OwnedKittiesTest::append(&0, 1);
+
+			let (page, cursor) = Module::<Test>::owned_kitties_page(0, None, 0);
+			assert_eq!(page, vec![]);
+			// nothing was read, so the cursor is the `start` that was passed in
+			assert_eq!(cursor, None);
+		});
+	}
+
+	#[test]
+	fn owned_kitties_page_starting_at_last_element_is_empty() {
+		new_test_ext().execute_with(|| {
+			OwnedKittiesTest::append(&0, 1);
+			OwnedKittiesTest::append(&0, 2);
+
+			let (page, cursor) = Module::<Test>::owned_kitties_page(0, Some(2), 5);
+			assert_eq!(page, vec![]);
+			assert_eq!(cursor, None);
+		});
+	}
 }
\ No newline at end of file